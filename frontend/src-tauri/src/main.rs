@@ -1,20 +1,279 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use std::collections::HashMap;
-use std::net::TcpStream;
+use std::collections::{HashMap, VecDeque};
+use std::fs::{self, OpenOptions};
+use std::io::{Read as _, Write as _};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::{Duration, Instant};
-use tauri::api::process::{Command, CommandEvent};
+use tauri::api::process::{Command, CommandChild, CommandEvent};
+use tauri::{Manager, RunEvent};
+
+mod update;
+
+use update::{resolve_release, ReleaseInfo, UpdateChannel};
 
 const BACKEND_HOST: &str = "127.0.0.1";
 const BACKEND_READY_TIMEOUT: Duration = Duration::from_secs(15);
 const BACKEND_READY_POLL: Duration = Duration::from_millis(120);
+const BACKEND_CONNECT_TIMEOUT: Duration = Duration::from_millis(250);
+/// Token the sidecar echoes on `GET /healthz` so we can tell our own backend
+/// apart from a foreign listener on the port. Passed down via `APP_HEALTH_MARKER`
+/// so the contract has a single source of truth rather than a literal the
+/// backend has to hardcode independently.
+const BACKEND_HEALTH_MARKER: &str = "interview-atlas";
+
+/// Handle to the spawned sidecar, kept in managed state so it can be killed on
+/// app exit instead of being orphaned and left holding the port.
+#[derive(Default)]
+struct BackendChild(Arc<Mutex<Option<CommandChild>>>);
+
+impl BackendChild {
+    fn store(&self, child: CommandChild) {
+        if let Ok(mut slot) = self.0.lock() {
+            *slot = Some(child);
+        }
+    }
+
+    fn kill(&self) {
+        if let Ok(mut slot) = self.0.lock() {
+            if let Some(child) = slot.take() {
+                let _ = child.kill();
+            }
+        }
+    }
+}
+
+/// How the shell talks to the sidecar's readiness endpoint. TCP is the
+/// historical default; `pipe` switches to an OS IPC object (a named pipe on
+/// Windows, a Unix-domain socket elsewhere) that avoids port conflicts and is
+/// only reachable by the current user.
+enum Transport {
+    Tcp { port: String },
+    Ipc { path: String },
+}
+
+impl Transport {
+    /// Resolve the transport from `APP_TRANSPORT` (`pipe`|`tcp`, default `tcp`).
+    fn from_env(port: &str) -> Self {
+        match std::env::var("APP_TRANSPORT").ok().as_deref() {
+            Some(value) if value.eq_ignore_ascii_case("pipe") => Self::Ipc {
+                path: ipc_path(port),
+            },
+            _ => Self::Tcp {
+                port: port.to_string(),
+            },
+        }
+    }
+
+    /// Environment passed down to the sidecar so it binds the same endpoint.
+    fn envs(&self) -> HashMap<String, String> {
+        let mut envs = HashMap::new();
+        match self {
+            Self::Tcp { .. } => {
+                envs.insert("APP_TRANSPORT".to_string(), "tcp".to_string());
+            }
+            Self::Ipc { path } => {
+                envs.insert("APP_TRANSPORT".to_string(), "pipe".to_string());
+                envs.insert("APP_SOCKET".to_string(), path.clone());
+            }
+        }
+        envs
+    }
+
+    /// A human-readable description of the endpoint for log lines.
+    fn describe(&self) -> String {
+        match self {
+            Self::Tcp { port } => format!("{BACKEND_HOST}:{port}"),
+            Self::Ipc { path } => path.clone(),
+        }
+    }
+
+    /// Whether the endpoint currently accepts a connection.
+    fn connectable(&self) -> bool {
+        match self {
+            Self::Tcp { port } => tcp_probe(port).is_some(),
+            Self::Ipc { path } => ipc_connectable(path),
+        }
+    }
+
+    /// Whether the endpoint is connectable *and* owned by our backend. Used
+    /// before reusing an already-listening process so we never adopt a foreign
+    /// listener that merely happens to hold the port.
+    fn healthy(&self) -> bool {
+        match self {
+            Self::Tcp { port } => match tcp_probe(port) {
+                Some(stream) => tcp_is_our_backend(stream),
+                None => false,
+            },
+            // IPC endpoints are user-scoped at a path we control, so
+            // connectability is sufficient proof of ownership.
+            Self::Ipc { path } => ipc_connectable(path),
+        }
+    }
+}
+
+/// Connect to the TCP health port with a bounded timeout and Nagle disabled so
+/// the readiness loop isn't stalled by slow OS connects or buffering.
+fn tcp_probe(port: &str) -> Option<TcpStream> {
+    let addr = format!("{BACKEND_HOST}:{port}")
+        .to_socket_addrs()
+        .ok()?
+        .next()?;
+    let stream = TcpStream::connect_timeout(&addr, BACKEND_CONNECT_TIMEOUT).ok()?;
+    let _ = stream.set_nodelay(true);
+    Some(stream)
+}
+
+/// Issue a tiny HTTP health probe and confirm the response comes from our
+/// sidecar rather than an unrelated process squatting on the port.
+fn tcp_is_our_backend(mut stream: TcpStream) -> bool {
+    let _ = stream.set_read_timeout(Some(BACKEND_CONNECT_TIMEOUT));
+    let _ = stream.set_write_timeout(Some(BACKEND_CONNECT_TIMEOUT));
+    if stream
+        .write_all(b"GET /healthz HTTP/1.0\r\nHost: localhost\r\n\r\n")
+        .is_err()
+    {
+        return false;
+    }
+    let mut response = String::new();
+    let _ = stream.take(2048).read_to_string(&mut response);
+    response.contains(BACKEND_HEALTH_MARKER)
+}
+
+/// Default IPC endpoint path, scoped to the current user where the platform
+/// allows it.
+fn ipc_path(port: &str) -> String {
+    if let Ok(path) = std::env::var("APP_SOCKET") {
+        return path;
+    }
+    #[cfg(windows)]
+    {
+        format!(r"\\.\pipe\interview-atlas-{port}")
+    }
+    #[cfg(not(windows))]
+    {
+        let dir = std::env::var("TMPDIR").unwrap_or_else(|_| "/tmp".to_string());
+        format!("{}/interview-atlas-{port}.sock", dir.trim_end_matches('/'))
+    }
+}
+
+#[cfg(windows)]
+fn ipc_connectable(path: &str) -> bool {
+    std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(path)
+        .is_ok()
+}
+
+#[cfg(not(windows))]
+fn ipc_connectable(path: &str) -> bool {
+    std::os::unix::net::UnixStream::connect(path).is_ok()
+}
+const BACKEND_LOG_CAPACITY: usize = 500;
+const BACKEND_LOG_FILE: &str = "backend.log";
+const BACKEND_LOG_MAX_BYTES: u64 = 1024 * 1024;
+
+/// Bounded in-memory ring buffer of recent sidecar output, mirrored to a
+/// rotating log file so a crash that kills the window still leaves a record.
+#[derive(Clone)]
+struct BackendLog {
+    lines: Arc<Mutex<VecDeque<String>>>,
+    file: Option<PathBuf>,
+}
+
+impl BackendLog {
+    fn new(file: Option<PathBuf>) -> Self {
+        Self {
+            lines: Arc::new(Mutex::new(VecDeque::with_capacity(BACKEND_LOG_CAPACITY))),
+            file,
+        }
+    }
+
+    fn push(&self, line: String) {
+        if let Ok(mut buf) = self.lines.lock() {
+            if buf.len() == BACKEND_LOG_CAPACITY {
+                buf.pop_front();
+            }
+            buf.push_back(line.clone());
+        }
+        self.append_to_file(&line);
+    }
+
+    fn snapshot(&self) -> Vec<String> {
+        self.lines
+            .lock()
+            .map(|buf| buf.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    fn append_to_file(&self, line: &str) {
+        let Some(path) = &self.file else { return };
+        if let Ok(meta) = fs::metadata(path) {
+            if meta.len() >= BACKEND_LOG_MAX_BYTES {
+                let _ = fs::rename(path, path.with_extension("log.1"));
+            }
+        }
+        if let Ok(mut f) = OpenOptions::new().create(true).append(true).open(path) {
+            let _ = writeln!(f, "{line}");
+        }
+    }
+}
+
+#[tauri::command]
+fn get_backend_log(log: tauri::State<'_, BackendLog>) -> Vec<String> {
+    log.snapshot()
+}
 
-fn wait_for_backend_ready(port: &str, timeout: Duration) -> bool {
-    let addr = format!("{BACKEND_HOST}:{port}");
+/// Resolve the newest applicable release from the configured RSS/Atom feed.
+///
+/// Returns `Ok(None)` when the feed holds nothing newer than the running
+/// version on the selected channel, and `Err` when the feed can't be fetched
+/// or parsed.
+#[tauri::command]
+async fn check_for_update(app: tauri::AppHandle) -> Result<Option<ReleaseInfo>, String> {
+    resolve_latest_release(&app).await.map_err(|err| err.to_string())
+}
+
+/// Resolve the newest applicable release for the running app, using the
+/// configured feed URL and channel.
+async fn resolve_latest_release(
+    app: &tauri::AppHandle,
+) -> Result<Option<ReleaseInfo>, update::UpdateError> {
+    let feed_url = std::env::var("UPDATE_FEED_URL").unwrap_or_else(|_| {
+        "https://github.com/alexllenaf/INTERLENA-updates/releases/latest/download/appcast.xml"
+            .to_string()
+    });
+    let channel = UpdateChannel::from_env();
+    resolve_release(&feed_url, &app.package_info().version, channel).await
+}
+
+/// Check the feed and, if a newer signed release exists, run the user-facing
+/// update flow. Runs on a dedicated OS thread (not an async-runtime worker) so
+/// the blocking feed fetch and updater calls never panic on a nested runtime.
+fn check_and_prompt_update(app: tauri::AppHandle) {
+    match tauri::async_runtime::block_on(resolve_latest_release(&app)) {
+        Ok(Some(release)) if release.signature_url.is_some() => {
+            update::run_update_flow(app, release)
+        }
+        Ok(_) => {}
+        Err(err) => eprintln!("update: {err}"),
+    }
+}
+
+/// Poll until our own backend answers on the endpoint, or the deadline passes.
+///
+/// This checks `healthy()` (identity), not bare connectability: if a foreign
+/// listener holds the port, our freshly spawned sidecar can't bind it, and we
+/// must not mistake the squatter accepting a connection for our backend being
+/// ready.
+fn wait_for_backend_ready(transport: &Transport, timeout: Duration) -> bool {
     let deadline = Instant::now() + timeout;
     while Instant::now() < deadline {
-        if TcpStream::connect(&addr).is_ok() {
+        if transport.healthy() {
             return true;
         }
         thread::sleep(BACKEND_READY_POLL);
@@ -23,13 +282,27 @@ fn wait_for_backend_ready(port: &str, timeout: Duration) -> bool {
 }
 
 fn spawn_backend(app: &tauri::App) -> bool {
+    let handle = app.handle();
+    let log = app.state::<BackendLog>().inner().clone();
     let port = std::env::var("APP_PORT").unwrap_or_else(|_| "8000".to_string());
-    if wait_for_backend_ready(&port, Duration::from_millis(250)) {
-        eprintln!("backend: Reusing existing backend on {BACKEND_HOST}:{port}");
-        return true;
+    let transport = Transport::from_env(&port);
+    let endpoint = transport.describe();
+
+    let _ = handle.emit_all("backend://starting", &port);
+
+    if transport.connectable() {
+        if transport.healthy() {
+            eprintln!("backend: Reusing existing backend on {endpoint}");
+            let _ = handle.emit_all("backend://ready", &port);
+            return true;
+        }
+        eprintln!("backend: {endpoint} is held by a foreign listener; starting our own");
     }
 
-    let feed_url = std::env::var("UPDATE_FEED_URL").unwrap_or_else(|_| {
+    // The sidecar consumes its own `latest.json`-style feed, distinct from the
+    // RSS/Atom feed the shell resolves via `UPDATE_FEED_URL`; keep them on
+    // separate env vars so one format can't clobber the other.
+    let feed_url = std::env::var("BACKEND_UPDATE_FEED_URL").unwrap_or_else(|_| {
         "https://github.com/alexllenaf/INTERLENA-updates/releases/latest/download/latest.json"
             .to_string()
     });
@@ -37,6 +310,11 @@ fn spawn_backend(app: &tauri::App) -> bool {
     let mut envs = HashMap::new();
     envs.insert("APP_VERSION".to_string(), version);
     envs.insert("UPDATE_FEED_URL".to_string(), feed_url);
+    envs.insert(
+        "APP_HEALTH_MARKER".to_string(),
+        BACKEND_HEALTH_MARKER.to_string(),
+    );
+    envs.extend(transport.envs());
     let sidecar = Command::new_sidecar("interview-atlas-backend")
         .map(|cmd| {
             cmd.args(["--host", "127.0.0.1", "--port", &port])
@@ -45,12 +323,17 @@ fn spawn_backend(app: &tauri::App) -> bool {
         });
 
     let started = match sidecar {
-        Ok(Ok((mut rx, _child))) => {
+        Ok(Ok((mut rx, child))) => {
+            app.state::<BackendChild>().store(child);
+            let handle = handle.clone();
+            let log = log.clone();
             tauri::async_runtime::spawn(async move {
                 while let Some(event) = rx.recv().await {
                     match event {
                         CommandEvent::Stderr(line) | CommandEvent::Stdout(line) => {
                             eprintln!("backend: {line}");
+                            log.push(line.clone());
+                            let _ = handle.emit_all("backend://log", line);
                         }
                         _ => {}
                     }
@@ -59,11 +342,17 @@ fn spawn_backend(app: &tauri::App) -> bool {
             true
         }
         Ok(Err(err)) => {
-            eprintln!("Failed to spawn backend sidecar: {err}");
+            let reason = format!("Failed to spawn backend sidecar: {err}");
+            eprintln!("{reason}");
+            log.push(reason.clone());
+            let _ = handle.emit_all("backend://failed", reason);
             false
         }
         Err(err) => {
-            eprintln!("Failed to resolve backend sidecar: {err}");
+            let reason = format!("Failed to resolve backend sidecar: {err}");
+            eprintln!("{reason}");
+            log.push(reason.clone());
+            let _ = handle.emit_all("backend://failed", reason);
             false
         }
     };
@@ -72,25 +361,50 @@ fn spawn_backend(app: &tauri::App) -> bool {
         return false;
     }
 
-    if !wait_for_backend_ready(&port, BACKEND_READY_TIMEOUT) {
-        eprintln!(
-            "Backend sidecar did not become ready on {BACKEND_HOST}:{port} within {:?}",
-            BACKEND_READY_TIMEOUT
+    if !wait_for_backend_ready(&transport, BACKEND_READY_TIMEOUT) {
+        let reason = format!(
+            "Backend sidecar did not become ready on {endpoint} within {BACKEND_READY_TIMEOUT:?}"
         );
+        eprintln!("{reason}");
+        log.push(reason.clone());
+        let _ = handle.emit_all("backend://failed", reason);
         return false;
     }
 
+    let _ = handle.emit_all("backend://ready", &port);
     true
 }
 
 fn main() {
     tauri::Builder::default()
         .setup(|app| {
+            let log_file = app.path_resolver().app_data_dir().map(|dir| {
+                let _ = fs::create_dir_all(&dir);
+                dir.join(BACKEND_LOG_FILE)
+            });
+            app.manage(BackendLog::new(log_file));
+            app.manage(BackendChild::default());
             if !spawn_backend(app) {
                 eprintln!("Desktop backend failed to initialize correctly.");
+            } else {
+                let handle = app.handle();
+                std::thread::spawn(move || check_and_prompt_update(handle));
             }
+
+            // Let the frontend trigger a re-check on demand.
+            let handle = app.handle();
+            app.listen_global("tauri://update", move |_event| {
+                let handle = handle.clone();
+                std::thread::spawn(move || check_and_prompt_update(handle));
+            });
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .invoke_handler(tauri::generate_handler![get_backend_log, check_for_update])
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|app, event| {
+            if let RunEvent::ExitRequested { .. } | RunEvent::Exit = event {
+                app.state::<BackendChild>().kill();
+            }
+        });
 }
@@ -0,0 +1,202 @@
+//! Desktop-side update resolution.
+//!
+//! Instead of handing a single `latest.json` URL to the backend, the shell
+//! parses an RSS/Atom release feed itself: every entry is treated as a
+//! release, and the newest entry whose version is greater than the running
+//! one — and whose channel the user has opted into — is surfaced to the UI.
+
+use std::fmt;
+
+use feed_rs::model::{Entry, Link};
+use semver::Version;
+use tauri::{AppHandle, Manager};
+
+/// Release channels the user can opt into. Pre-release entries are skipped
+/// unless the `beta` channel is selected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UpdateChannel {
+    Stable,
+    Beta,
+}
+
+impl UpdateChannel {
+    /// Resolve the channel from `UPDATE_CHANNEL`, defaulting to `stable`.
+    pub fn from_env() -> Self {
+        match std::env::var("UPDATE_CHANNEL").ok().as_deref() {
+            Some(value) if value.eq_ignore_ascii_case("beta") => Self::Beta,
+            _ => Self::Stable,
+        }
+    }
+
+    fn accepts(self, version: &Version) -> bool {
+        match self {
+            Self::Beta => true,
+            Self::Stable => version.pre.is_empty(),
+        }
+    }
+}
+
+/// Metadata for a single resolved release, serialized to the frontend so the
+/// UI can show "what's new" before downloading.
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct ReleaseInfo {
+    pub version: String,
+    pub changelog: String,
+    pub download_url: Option<String>,
+    pub signature_url: Option<String>,
+}
+
+/// Errors raised while fetching or parsing an update feed.
+#[derive(Debug)]
+pub enum UpdateError {
+    Fetch(String),
+    Parse(String),
+}
+
+impl fmt::Display for UpdateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Fetch(msg) => write!(f, "failed to fetch update feed: {msg}"),
+            Self::Parse(msg) => write!(f, "failed to parse update feed: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for UpdateError {}
+
+/// Fetch `feed_url`, pick the newest entry newer than `current` on `channel`,
+/// and return its release metadata (or `None` if nothing qualifies).
+///
+/// Async so callers on the command-dispatch thread can `.await` the round-trip
+/// instead of blocking it; the OS-thread callers wrap this in `block_on`.
+pub async fn resolve_release(
+    feed_url: &str,
+    current: &Version,
+    channel: UpdateChannel,
+) -> Result<Option<ReleaseInfo>, UpdateError> {
+    let client = tauri::api::http::ClientBuilder::new()
+        .build()
+        .map_err(|err| UpdateError::Fetch(err.to_string()))?;
+    let request = tauri::api::http::HttpRequestBuilder::new("GET", feed_url)
+        .map_err(|err| UpdateError::Fetch(err.to_string()))?
+        .response_type(tauri::api::http::ResponseType::Text);
+    let body = client
+        .send(request)
+        .await
+        .and_then(|response| response.read_raw())
+        .map_err(|err| UpdateError::Fetch(err.to_string()))?;
+
+    let feed = feed_rs::parser::parse(body.as_slice())
+        .map_err(|err| UpdateError::Parse(err.to_string()))?;
+
+    Ok(select_release(&feed.entries, current, channel))
+}
+
+/// Pure selection step, kept separate from I/O so it can be reasoned about in
+/// isolation: the highest-versioned entry that beats `current` on `channel`.
+fn select_release(
+    entries: &[Entry],
+    current: &Version,
+    channel: UpdateChannel,
+) -> Option<ReleaseInfo> {
+    entries
+        .iter()
+        .filter_map(|entry| entry_version(entry).map(|version| (version, entry)))
+        .filter(|(version, _)| version > current && channel.accepts(version))
+        .max_by(|(a, _), (b, _)| a.cmp(b))
+        .map(|(version, entry)| ReleaseInfo {
+            version: version.to_string(),
+            changelog: entry
+                .content
+                .as_ref()
+                .and_then(|content| content.body.clone())
+                .or_else(|| entry.summary.as_ref().map(|text| text.content.clone()))
+                .unwrap_or_default(),
+            download_url: enclosure(entry, |media| {
+                !media.ends_with(".sig") && !media.ends_with(".sha256")
+            }),
+            signature_url: enclosure(entry, |media| media.ends_with(".sig")),
+        })
+}
+
+/// Parse a release version from the entry title, falling back to its id.
+fn entry_version(entry: &Entry) -> Option<Version> {
+    let from_title = entry
+        .title
+        .as_ref()
+        .and_then(|text| parse_version(&text.content));
+    from_title.or_else(|| parse_version(&entry.id))
+}
+
+/// Lenient version parse that tolerates a leading `v`/`release-` and trailing
+/// path segments (e.g. a tag URL in the entry id).
+fn parse_version(raw: &str) -> Option<Version> {
+    let token = raw
+        .rsplit(['/', ' ', '\t'])
+        .find(|segment| segment.chars().any(|c| c.is_ascii_digit()))?;
+    let trimmed = token.trim_start_matches(|c: char| !c.is_ascii_digit());
+    Version::parse(trimmed).ok()
+}
+
+/// First enclosure link whose href satisfies `accept`.
+fn enclosure(entry: &Entry, accept: impl Fn(&str) -> bool) -> Option<String> {
+    entry
+        .links
+        .iter()
+        .filter(|link| is_download(link))
+        .map(|link| link.href.clone())
+        .find(|href| accept(href))
+}
+
+/// Only true enclosures are downloadable artifacts. `alternate` links are
+/// human-facing release pages (e.g. GitHub's Atom feed), not something we can
+/// download and install, so treating them as artifacts would resolve the
+/// release page as the "download" and leave the signature unresolved.
+fn is_download(link: &Link) -> bool {
+    link.rel.as_deref() == Some("enclosure")
+}
+
+/// Prompt the user about a resolved release and, on confirmation, drive
+/// Tauri's bundle updater to download, verify, and install it.
+///
+/// Runs on a dedicated OS thread (see `check_and_prompt_update`), so the
+/// blocking dialog and the `block_on`-driven updater calls never execute on an
+/// async-runtime worker.
+pub fn run_update_flow(app: AppHandle, release: ReleaseInfo) {
+    let prompt = format!(
+        "Update available \u{2192} {} \u{2014} install & restart?",
+        release.version
+    );
+    if !tauri::api::dialog::blocking::ask(app.get_window("main").as_ref(), "Update", prompt) {
+        return;
+    }
+
+    // Hand the actual download/verify/install to Tauri's updater: it fetches
+    // the signed package, verifies it against the minisign public key from
+    // `tauri.conf.json`, stages the payload, and relaunches on success.
+    //
+    // Tauri resolves its own endpoint independently of our RSS channel filter,
+    // so gate the install on the version the channel actually approved: if the
+    // endpoint offers anything else (e.g. a pre-release a `stable` user opted
+    // out of), refuse rather than install something `select_release` rejected.
+    match tauri::async_runtime::block_on(tauri::updater::builder(app).check()) {
+        Ok(update) if update.is_update_available() => {
+            if update.latest_version() != release.version {
+                eprintln!(
+                    "update: channel resolved {} but updater endpoint offers {}; refusing to install",
+                    release.version,
+                    update.latest_version()
+                );
+                return;
+            }
+            if let Err(err) = tauri::async_runtime::block_on(update.download_and_install()) {
+                eprintln!("update: failed to install {}: {err}", release.version);
+            }
+        }
+        Ok(_) => eprintln!(
+            "update: feed advertised {} but the updater found no signed package",
+            release.version
+        ),
+        Err(err) => eprintln!("update: updater check failed: {err}"),
+    }
+}